@@ -0,0 +1,179 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::Rest;
+
+use snarkos_node_consensus::Consensus;
+use snarkos_node_router::Routing;
+use snarkvm::{prelude::Network, synthesizer::ConsensusStorage};
+
+use axum::{
+    extract::State,
+    http::{header::AUTHORIZATION, Request, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// The claims encoded in a REST API bearer token.
+#[derive(Serialize, Deserialize)]
+pub struct Claims {
+    /// The subject the token was issued to (e.g. an operator or service name).
+    pub sub: String,
+    /// The expiration time, in seconds since the Unix epoch.
+    pub exp: usize,
+    /// The set of scopes the token grants (e.g. `"broadcast"`, `"records"`, `"admin"`).
+    #[serde(default)]
+    pub scope: Vec<String>,
+}
+
+impl Claims {
+    /// Returns `true` if the claims grant the given `scope`, or carry the `admin` scope.
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scope.iter().any(|s| s == scope || s == "admin")
+    }
+}
+
+/// A single rule mapping a route (matched by path suffix or substring) to the scope required to
+/// access it.
+///
+/// Matching is done on the route's suffix/substring (rather than a full path) since every route
+/// is registered under the node's active network name, e.g. `/testnet3/transaction/broadcast`.
+#[derive(Clone)]
+pub enum RoutePattern {
+    /// Matches any path ending with this suffix, e.g. `/transaction/broadcast`.
+    Suffix(&'static str),
+    /// Matches any path containing this substring, e.g. `/records/all/`.
+    Contains(&'static str),
+}
+
+impl RoutePattern {
+    fn matches(&self, path: &str) -> bool {
+        match self {
+            Self::Suffix(suffix) => path.ends_with(suffix),
+            Self::Contains(substring) => path.contains(substring),
+        }
+    }
+}
+
+/// A route pattern paired with the scope a bearer token must carry to access it.
+#[derive(Clone)]
+pub struct ScopeRule {
+    pattern: RoutePattern,
+    scope: &'static str,
+}
+
+impl ScopeRule {
+    /// Creates a rule requiring `scope` for any path ending with `suffix`.
+    pub const fn suffix(suffix: &'static str, scope: &'static str) -> Self {
+        Self { pattern: RoutePattern::Suffix(suffix), scope }
+    }
+
+    /// Creates a rule requiring `scope` for any path containing `substring`.
+    pub const fn contains(substring: &'static str, scope: &'static str) -> Self {
+        Self { pattern: RoutePattern::Contains(substring), scope }
+    }
+}
+
+/// The default protected-route set: only routes that can broadcast transactions, leak decrypted
+/// records, or expose node internals require a token. Read-only ledger endpoints are
+/// intentionally left out.
+pub fn default_protected_routes() -> Vec<ScopeRule> {
+    vec![
+        ScopeRule::suffix("/transaction/broadcast", "broadcast"),
+        ScopeRule::suffix("/node/address", "admin"),
+        ScopeRule::contains("/records/all/", "records"),
+        ScopeRule::contains("/records/spent/", "records"),
+        ScopeRule::contains("/records/unspent/", "records"),
+    ]
+}
+
+/// The configuration used to verify bearer tokens presented to the REST server.
+pub struct AuthConfig {
+    /// The key used to verify the token signature.
+    decoding_key: DecodingKey,
+    /// The validation rules (algorithm, expiration, etc.) applied to incoming tokens.
+    validation: Validation,
+    /// The routes that require a bearer token, and the scope each one requires.
+    protected_routes: Vec<ScopeRule>,
+}
+
+impl AuthConfig {
+    /// Initializes a new auth configuration from an HMAC secret, protecting the default route
+    /// set (see [`default_protected_routes`]).
+    pub fn new(secret: &[u8]) -> Self {
+        Self::with_protected_routes(secret, default_protected_routes())
+    }
+
+    /// Initializes a new auth configuration from an HMAC secret, protecting exactly the given
+    /// routes. This lets an operator running a public gateway lock down additional routes (or
+    /// loosen the defaults) without forking this crate.
+    pub fn with_protected_routes(secret: &[u8], protected_routes: Vec<ScopeRule>) -> Self {
+        Self {
+            decoding_key: DecodingKey::from_secret(secret),
+            validation: Validation::new(Algorithm::HS256),
+            protected_routes,
+        }
+    }
+
+    /// Returns the scope required to access the given path, if the route is protected.
+    fn required_scope(&self, path: &str) -> Option<&'static str> {
+        self.protected_routes.iter().find(|rule| rule.pattern.matches(path)).map(|rule| rule.scope)
+    }
+
+    /// Decodes and validates the given bearer token, returning its claims.
+    fn verify(&self, token: &str) -> Result<Claims, StatusCode> {
+        decode::<Claims>(token, &self.decoding_key, &self.validation)
+            .map(|data| data.claims)
+            .map_err(|_| StatusCode::UNAUTHORIZED)
+    }
+}
+
+/// The middleware used to enforce JWT-based authentication and scoping on protected routes.
+pub async fn auth_middleware<N: Network, C: 'static + ConsensusStorage<N>, R: Routing<N>, B: Send>(
+    State(rest): State<Rest<N, C, R>>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Result<Response, StatusCode> {
+    // If no auth config is set, the operator hasn't opted into protecting this deployment.
+    let Some(auth) = rest.auth() else {
+        return Ok(next.run(request).await);
+    };
+
+    // Determine the scope required by the requested path.
+    let Some(scope) = auth.required_scope(request.uri().path()) else {
+        // The route isn't in the protected set, so let it through.
+        return Ok(next.run(request).await);
+    };
+
+    // Extract the bearer token from the `Authorization` header.
+    let token = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    // Verify the token and check that it carries the required scope.
+    let claims = auth.verify(token)?;
+    if !claims.has_scope(scope) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok(next.run(request).await)
+}
+
+pub(crate) type SharedAuthConfig = Arc<AuthConfig>;
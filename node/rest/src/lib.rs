@@ -17,11 +17,24 @@
 #[macro_use]
 extern crate tracing;
 
+mod auth;
+use auth::{auth_middleware, AuthConfig, SharedAuthConfig};
+pub use auth::ScopeRule;
+
 mod helpers;
 pub use helpers::*;
 
+mod rate_limit;
+use rate_limit::{rate_limit_middleware, RateLimitConfig, RateLimiter};
+
 mod routes;
 
+mod tls;
+use tls::TlsConfig;
+
+mod websocket;
+use websocket::Hub;
+
 use snarkos_node_consensus::Consensus;
 use snarkos_node_messages::{Data, Message, UnconfirmedTransaction};
 use snarkos_node_router::Routing;
@@ -43,7 +56,9 @@ use axum::{
 };
 use axum_extra::response::ErasedJson;
 use parking_lot::Mutex;
-use std::{net::SocketAddr, sync::Arc};
+use serde::Deserialize;
+use serde_json::json;
+use std::{net::SocketAddr, path::PathBuf, sync::Arc, time::Duration};
 use tokio::task::JoinHandle;
 use tower_http::{
     cors::{Any, CorsLayer},
@@ -59,22 +74,63 @@ pub struct Rest<N: Network, C: ConsensusStorage<N>, R: Routing<N>> {
     ledger: Ledger<N, C>,
     /// The node (routing).
     routing: Arc<R>,
+    /// The JWT auth configuration for the protected routes, if enabled.
+    auth: Option<SharedAuthConfig>,
+    /// The TLS certificate and key paths used to terminate the REST server, if enabled.
+    tls: Option<TlsConfig>,
+    /// The per-IP rate limiter, if enabled.
+    rate_limiter: Option<Arc<RateLimiter>>,
+    /// The fan-out hub for the `/subscribe` websocket route.
+    hub: Arc<Hub>,
     /// The server handles.
     handles: Arc<Mutex<Vec<JoinHandle<()>>>>,
 }
 
 impl<N: Network, C: 'static + ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
     /// Initializes a new instance of the server.
+    ///
+    /// If `jwt_secret` is set, the sensitive routes (transaction broadcast, record decryption,
+    /// and node address) require a bearer token signed with that secret and carrying the
+    /// matching scope. If it's `None`, those routes remain open, preserving prior behavior.
+    ///
+    /// `protected_routes` overrides which routes require which scope; pass `None` to protect
+    /// [`auth::default_protected_routes`]. This lets an operator running a public gateway lock
+    /// down additional routes or loosen the defaults. It has no effect if `jwt_secret` is `None`.
+    ///
+    /// If `tls` is set (as a `(cert_path, key_path)` pair of PEM files), the server terminates
+    /// TLS directly and reloads the certificate in place if the files change on disk. If it's
+    /// `None`, the server falls back to plaintext HTTP, as before.
+    ///
+    /// If `rate_limit` is set, each peer address is throttled by a token-bucket limiter, with a
+    /// stricter bucket for expensive/write routes. If it's `None`, requests aren't throttled.
     pub fn start(
         rest_ip: SocketAddr,
         consensus: Option<Consensus<N, C>>,
         ledger: Ledger<N, C>,
         routing: Arc<R>,
+        jwt_secret: Option<Vec<u8>>,
+        protected_routes: Option<Vec<ScopeRule>>,
+        tls: Option<(PathBuf, PathBuf)>,
+        rate_limit: Option<RateLimitConfig>,
     ) -> Result<Self> {
+        // Initialize the auth configuration, if a secret was provided.
+        let auth = jwt_secret.map(|secret| {
+            Arc::new(match protected_routes {
+                Some(protected_routes) => AuthConfig::with_protected_routes(&secret, protected_routes),
+                None => AuthConfig::new(&secret),
+            })
+        });
+        // Initialize the TLS configuration, if cert and key paths were provided.
+        let tls = tls.map(|(cert_path, key_path)| TlsConfig::new(cert_path, key_path));
+        // Initialize the rate limiter, if a configuration was provided.
+        let rate_limiter = rate_limit.map(|config| Arc::new(RateLimiter::new(config)));
         // Initialize the server.
-        let mut server = Self { consensus, ledger, routing, handles: Default::default() };
+        let mut server =
+            Self { consensus, ledger, routing, auth, tls, rate_limiter, hub: Default::default(), handles: Default::default() };
         // Spawn the server.
         server.spawn_server(rest_ip);
+        // Spawn the watcher that fans out newly committed blocks to websocket subscribers.
+        server.spawn_block_watcher();
         // Return the server.
         Ok(server)
     }
@@ -90,61 +146,275 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
     pub const fn handles(&self) -> &Arc<Mutex<Vec<JoinHandle<()>>>> {
         &self.handles
     }
+
+    /// Returns the JWT auth configuration, if enabled.
+    pub(crate) fn auth(&self) -> Option<&SharedAuthConfig> {
+        self.auth.as_ref()
+    }
+
+    /// Returns the rate limiter, if enabled.
+    pub(crate) fn rate_limiter(&self) -> Option<&Arc<RateLimiter>> {
+        self.rate_limiter.as_ref()
+    }
+
+    /// Returns the fan-out hub for the `/subscribe` websocket route.
+    pub(crate) fn hub(&self) -> &Hub {
+        &self.hub
+    }
+
+    /// Publishes `payload` to every websocket subscriber listening on `topic` (`"blocks"` or
+    /// `"transactions"`).
+    ///
+    /// Exposed so that callers outside this crate can also push events directly, e.g. once a
+    /// tighter consensus integration replaces [`Self::spawn_block_watcher`]'s polling.
+    pub fn publish(&self, topic: impl Into<String>, payload: serde_json::Value) {
+        self.hub.publish(topic, payload);
+    }
+}
+
+/// The maximum number of blocks that `feeHistory` will walk back over, to bound the work done
+/// by a single request.
+const MAX_FEE_HISTORY_BLOCKS: u32 = 1024;
+
+/// The default reward percentiles used by `feeHistory` when `reward_percentiles` isn't set.
+const DEFAULT_REWARD_PERCENTILES: [f64; 3] = [25.0, 50.0, 75.0];
+
+/// How often the block watcher polls the ledger's canonical height for new blocks to fan out to
+/// websocket subscribers.
+///
+/// This is a deliberate, interim fallback: `Consensus`/`Ledger` don't currently expose a commit
+/// hook to this crate (e.g. a `tokio::sync::broadcast` the BFT/ledger-writer side pushes into on
+/// every accepted block), so there's nothing to subscribe to here instead of polling. A short
+/// interval is used to keep the added confirmation latency for subscribers well under a second;
+/// switching `Rest` to an injected commit-notification channel instead of reading
+/// `Ledger::latest_height` in a loop would remove this latency and the redundant height reads
+/// entirely, and should replace this the next time `Consensus`'s commit path grows that hook.
+const BLOCK_WATCH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The query parameters accepted by `feeHistory`.
+#[derive(Deserialize)]
+pub struct FeeHistoryQuery {
+    /// A comma-separated list of percentiles (0-100) to sample from each block's priority fees.
+    reward_percentiles: Option<String>,
+}
+
+/// Returns the index into a sorted slice of length `n` for the given `percentile` (0-100),
+/// via `ceil(percentile / 100 * n) - 1`. Used for both the priority-fee reward percentiles and
+/// the per-block base fee (as the median, i.e. the 50th percentile).
+fn percentile_index(percentile: f64, n: usize) -> usize {
+    let index = ((percentile / 100.0) * n as f64).ceil() as usize;
+    index.saturating_sub(1).min(n - 1)
+}
+
+/// Returns the network's per-block fee-weight limit: the maximum number of transactions allowed
+/// in a block times the maximum spend (execution cost) allowed per transaction. This is the
+/// actual consensus-enforced ceiling on total transaction fees in a block, rather than a made-up
+/// figure, so `capacity_used_ratio` below means something.
+fn block_fee_weight_limit<N: Network>() -> u64 {
+    N::MAX_TRANSACTIONS_PER_BLOCK as u64 * N::TRANSACTION_SPEND_LIMIT
 }
 
 impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
+    /// GET /testnet3/feeHistory/{block_count}
+    ///
+    /// Returns a short history of base fees and priority-fee percentiles over the last
+    /// `block_count` blocks, so that a caller can estimate a reasonable priority fee before
+    /// calling `transaction/broadcast`.
+    async fn get_fee_history(
+        State(rest): State<Self>,
+        Path(block_count): Path<u32>,
+        Query(query): Query<FeeHistoryQuery>,
+    ) -> Result<ErasedJson, StatusCode> {
+        // Parse the requested reward percentiles, falling back to the defaults.
+        let percentiles = match query.reward_percentiles {
+            Some(raw) => raw
+                .split(',')
+                .map(|s| s.trim().parse::<f64>().map_err(|_| StatusCode::BAD_REQUEST))
+                .collect::<Result<Vec<_>, _>>()?,
+            None => DEFAULT_REWARD_PERCENTILES.to_vec(),
+        };
+
+        // Cap the number of blocks scanned to avoid an unbounded walk back.
+        let block_count = block_count.clamp(1, MAX_FEE_HISTORY_BLOCKS);
+
+        let latest_height = rest.ledger.latest_height();
+        let oldest_height = latest_height.saturating_sub(block_count - 1);
+
+        let mut base_fees = Vec::with_capacity(block_count as usize);
+        let mut rewards: Vec<Vec<u64>> = Vec::with_capacity(block_count as usize);
+        let mut capacity_used_ratios = Vec::with_capacity(block_count as usize);
+
+        let fee_weight_limit = block_fee_weight_limit::<N>();
+
+        for height in oldest_height..=latest_height {
+            let block = rest.ledger.get_block(height).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+            // Collect the (base fee, priority fee) pair for each transaction in the block, and
+            // sum the total fee weight spent so the block's capacity usage can be reported too.
+            let mut base_fees_in_block = Vec::new();
+            let mut priority_fees = Vec::new();
+            let mut total_fee_weight: u64 = 0;
+
+            for transaction in block.transactions().iter() {
+                let fee: u64 = *transaction.fee_amount().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+                let priority_fee: u64 = *transaction.priority_fee_amount().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+                base_fees_in_block.push(fee.saturating_sub(priority_fee));
+                priority_fees.push(priority_fee);
+                total_fee_weight = total_fee_weight.saturating_add(fee);
+            }
+
+            capacity_used_ratios.push(total_fee_weight as f64 / fee_weight_limit as f64);
+
+            if base_fees_in_block.is_empty() {
+                // An empty block repeats the previous block's base fee and percentiles.
+                base_fees.push(base_fees.last().copied().unwrap_or(0));
+                rewards.push(rewards.last().cloned().unwrap_or_else(|| vec![0; percentiles.len()]));
+            } else {
+                base_fees_in_block.sort_unstable();
+                base_fees.push(base_fees_in_block[percentile_index(50.0, base_fees_in_block.len())]);
+
+                priority_fees.sort_unstable();
+                let n = priority_fees.len();
+                let block_rewards =
+                    percentiles.iter().map(|percentile| priority_fees[percentile_index(*percentile, n)]).collect();
+                rewards.push(block_rewards);
+            }
+        }
+
+        Ok(ErasedJson::new(json!({
+            "oldest_height": oldest_height,
+            "base_fees": base_fees,
+            "reward": rewards,
+            "capacity_used_ratio": capacity_used_ratios,
+        })))
+    }
+}
+
+/// Aleo's mainnet network ID. `N::ID` for every other network is a testnet or canary iteration
+/// number, so this is the one value that needs its own stem rather than `testnet{N::ID}`.
+const MAINNET_ID: u16 = 0;
+
+/// Aleo's canary network ID.
+const CANARY_ID: u16 = 1;
+
+/// Returns the URL path prefix used for `N`'s REST routes, e.g. `testnet3` or `mainnet`.
+///
+/// This is derived from `N::ID`, not `N::NAME`: `N::NAME` is a human-readable display string
+/// (e.g. `"Aleo Testnet 3"`), and nesting the router under it would put literal spaces in every
+/// route and break every existing client. `N::ID` is the network's stable numeric identifier, but
+/// it isn't a testnet prefix for every network: mainnet is `N::ID == 0` and canary is
+/// `N::ID == 1`, so those two map to their own stems; every other ID is a testnet and keeps the
+/// prior `testnet{N::ID}` form, which reproduces today's hardcoded `testnet3` prefix exactly.
+fn network_prefix<N: Network>() -> String {
+    network_prefix_for_id(N::ID)
+}
+
+/// Returns the URL path prefix for the network with the given `N::ID` (see [`network_prefix`]).
+fn network_prefix_for_id(id: u16) -> String {
+    match id {
+        MAINNET_ID => "mainnet".to_string(),
+        CANARY_ID => "canary".to_string(),
+        id => format!("testnet{id}"),
+    }
+}
+
+#[cfg(test)]
+mod network_prefix_tests {
+    use super::*;
+    use snarkvm::prelude::Testnet3;
+
+    #[test]
+    fn testnet3_prefix_is_unchanged() {
+        assert_eq!(network_prefix::<Testnet3>(), "testnet3");
+    }
+
+    #[test]
+    fn mainnet_and_canary_get_their_own_stem() {
+        assert_eq!(network_prefix_for_id(MAINNET_ID), "mainnet");
+        assert_eq!(network_prefix_for_id(CANARY_ID), "canary");
+        assert_eq!(network_prefix_for_id(3), "testnet3");
+    }
+}
+
+impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
+    /// Returns the router for the routes that are protected by JWT auth when it's enabled,
+    /// i.e. transaction broadcast, decrypted records, and the node's address.
+    fn protected_routes(&self) -> axum::Router<Self> {
+        axum::Router::new()
+            .route("/transaction/broadcast", post(Self::transaction_broadcast))
+            .route("/node/address", get(Self::get_node_address))
+            .route("/records/all/:view_key", get(Self::get_records_all))
+            .route("/records/spent/:view_key", get(Self::get_records_spent))
+            .route("/records/unspent/:view_key", get(Self::get_records_unspent))
+            .route_layer(middleware::from_fn_with_state(self.clone(), auth_middleware::<N, C, R, axum::body::Body>))
+    }
+
     fn spawn_server(&mut self, rest_ip: SocketAddr) {
         let cors = CorsLayer::new()
             .allow_origin(Any)
             .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
             .allow_headers([CONTENT_TYPE]);
 
-        let router = {
-            axum::Router::new()
+        // All routes are registered once, unprefixed, and then nested under the node's active
+        // network name below. A request for a different (or missing) network segment simply
+        // doesn't match the nest and falls through to a 404, so the REST surface is
+        // forward-compatible across network upgrades instead of being baked to one literal.
+        let network_routes = axum::Router::new()
 
             // GET ../latest/..
-            .route("/testnet3/latest/height", get(Self::latest_height))
-            .route("/testnet3/latest/hash", get(Self::latest_hash))
-            .route("/testnet3/latest/block", get(Self::latest_block))
-            .route("/testnet3/latest/stateRoot", get(Self::latest_state_root))
+            .route("/latest/height", get(Self::latest_height))
+            .route("/latest/hash", get(Self::latest_hash))
+            .route("/latest/block", get(Self::latest_block))
+            .route("/latest/stateRoot", get(Self::latest_state_root))
 
             // GET ../block/..
-            .route("/testnet3/block/:height_or_hash", get(Self::get_block))
+            .route("/block/:height_or_hash", get(Self::get_block))
             // The path param here is actually only the height, but the name must match the route
             // above, otherwise there'll be a conflict at runtime.
-            .route("/testnet3/block/:height_or_hash/transactions", get(Self::get_block_transactions))
+            .route("/block/:height_or_hash/transactions", get(Self::get_block_transactions))
 
-            // GET and POST ../transaction/..
-            .route("/testnet3/transaction/:id", get(Self::get_transaction))
-            .route("/testnet3/transaction/broadcast", post(Self::transaction_broadcast))
+            // GET ../transaction/..
+            .route("/transaction/:id", get(Self::get_transaction))
 
             // GET ../find/..
-            .route("/testnet3/find/blockHash/:tx_id", get(Self::find_block_hash))
-            .route("/testnet3/find/transactionID/deployment/:program_id", get(Self::find_transaction_id_from_program_id))
-            .route("/testnet3/find/transactionID/:transition_id", get(Self::find_transaction_id_from_transition_id))
-            .route("/testnet3/find/transitionID/:input_or_output_id", get(Self::find_transition_id))
+            .route("/find/blockHash/:tx_id", get(Self::find_block_hash))
+            .route("/find/transactionID/deployment/:program_id", get(Self::find_transaction_id_from_program_id))
+            .route("/find/transactionID/:transition_id", get(Self::find_transaction_id_from_transition_id))
+            .route("/find/transitionID/:input_or_output_id", get(Self::find_transition_id))
 
             // GET ../peers/..
-            .route("/testnet3/peers/count", get(Self::get_peers_count))
-            .route("/testnet3/peers/all", get(Self::get_peers_all))
-            .route("/testnet3/peers/all/metrics", get(Self::get_peers_all_metrics))
+            .route("/peers/count", get(Self::get_peers_count))
+            .route("/peers/all", get(Self::get_peers_all))
+            .route("/peers/all/metrics", get(Self::get_peers_all_metrics))
 
             // GET ../program/..
-            .route("/testnet3/program/:id", get(Self::get_program))
-            .route("/testnet3/program/:id/mappings", get(Self::get_mapping_names))
-            .route("/testnet3/program/:id/mapping/:name/:key", get(Self::get_mapping_value))
+            .route("/program/:id", get(Self::get_program))
+            .route("/program/:id/mappings", get(Self::get_mapping_names))
+            .route("/program/:id/mapping/:name/:key", get(Self::get_mapping_value))
+
+            // GET ../feeHistory/..
+            .route("/feeHistory/:block_count", get(Self::get_fee_history))
+
+            // GET ../subscribe (websocket upgrade)
+            .route("/subscribe", get(websocket::subscribe::<N, C, R>))
 
             // GET misc endpoints.
-            .route("/testnet3/blocks", get(Self::get_blocks))
-            .route("/testnet3/height/:hash", get(Self::get_height))
-            .route("/testnet3/memoryPool/transactions", get(Self::get_memory_pool_transactions))
-            .route("/testnet3/statePath/:commitment", get(Self::get_state_path_for_commitment))
-            .route("/testnet3/beacons", get(Self::get_beacons))
-            .route("/testnet3/node/address", get(Self::get_node_address))
-            .route("/testnet3/node/env", get(Self::get_env_info))
-            .route("/testnet3/records/all/:view_key", get(Self::get_records_all))
-            .route("/testnet3/records/spent/:view_key", get(Self::get_records_spent))
-            .route("/testnet3/records/unspent/:view_key", get(Self::get_records_unspent))
+            .route("/blocks", get(Self::get_blocks))
+            .route("/height/:hash", get(Self::get_height))
+            .route("/memoryPool/transactions", get(Self::get_memory_pool_transactions))
+            .route("/statePath/:commitment", get(Self::get_state_path_for_commitment))
+            .route("/beacons", get(Self::get_beacons))
+            .route("/node/env", get(Self::get_env_info))
+
+            // Merge in the routes that require a scoped bearer token by default when auth is enabled.
+            .merge(self.protected_routes())
+            // Check every route against the configured protected set when auth is enabled.
+            .route_layer(middleware::from_fn_with_state(self.clone(), auth_middleware::<N, C, R, axum::body::Body>));
+
+        let router = {
+            axum::Router::new()
+            // Register every route under the node's active network name, e.g. `/testnet3/..`.
+            .nest(&format!("/{}", network_prefix::<N>()), network_routes)
 
             // Pass in `Rest` to make things convenient.
             .with_state(self.clone())
@@ -152,21 +422,61 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
             .layer(TraceLayer::new_for_http())
             // Custom logging.
             .layer(middleware::from_fn(log_middleware))
+            // Per-IP rate limiting.
+            .layer(middleware::from_fn_with_state(self.clone(), rate_limit_middleware::<N, C, R, axum::body::Body>))
             // Enable CORS.
             .layer(cors)
             // Cap body size at 10MB.
             .layer(DefaultBodyLimit::max(10 * 1024 * 1024))
-            // JWT auth.
-            // .layer(middleware::from_fn(auth_middleware))
         };
 
+        let tls = self.tls.clone();
+        let handles = self.handles.clone();
         self.handles.lock().push(tokio::spawn(async move {
-            axum::Server::bind(&rest_ip)
-                .serve(router.into_make_service_with_connect_info::<SocketAddr>())
-                .await
-                .expect("couldn't start rest server");
+            let service = router.into_make_service_with_connect_info::<SocketAddr>();
+            match tls {
+                // Serve over TLS, reloading the certificate in place if it changes on disk.
+                Some(tls) => {
+                    let config = tls.load().await.expect("couldn't load the REST server's TLS certificate");
+                    handles.lock().push(tls.spawn_reloader(config.clone()));
+                    axum_server::bind_rustls(rest_ip, config).serve(service).await.expect("couldn't start rest server");
+                }
+                // Fall back to plaintext HTTP.
+                None => {
+                    axum::Server::bind(&rest_ip).serve(service).await.expect("couldn't start rest server");
+                }
+            }
         }))
     }
+
+    /// Spawns a background task that watches the ledger's canonical height and publishes newly
+    /// committed blocks (and their transactions) to websocket subscribers.
+    ///
+    /// See [`BLOCK_WATCH_INTERVAL`] for why this polls rather than subscribing to a commit hook.
+    fn spawn_block_watcher(&self) {
+        let rest = self.clone();
+        let handle = tokio::spawn(async move {
+            let mut last_height = rest.ledger.latest_height();
+            loop {
+                tokio::time::sleep(BLOCK_WATCH_INTERVAL).await;
+
+                let height = rest.ledger.latest_height();
+                for height in (last_height + 1)..=height {
+                    match rest.ledger.get_block(height) {
+                        Ok(block) => {
+                            rest.publish("blocks", json!({ "height": height, "hash": block.hash() }));
+                            for transaction in block.transactions().iter() {
+                                rest.publish("transactions", json!({ "height": height, "id": transaction.id() }));
+                            }
+                        }
+                        Err(error) => warn!("Failed to fetch block {height} for websocket fan-out: {error}"),
+                    }
+                }
+                last_height = height;
+            }
+        });
+        self.handles.lock().push(handle);
+    }
 }
 
 async fn log_middleware<B>(
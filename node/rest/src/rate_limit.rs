@@ -0,0 +1,193 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::Rest;
+
+use snarkos_node_consensus::Consensus;
+use snarkos_node_router::Routing;
+use snarkvm::{prelude::Network, synthesizer::ConsensusStorage};
+
+use axum::{
+    extract::{ConnectInfo, State},
+    http::{header::RETRY_AFTER, HeaderValue, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use parking_lot::Mutex;
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+/// The per-route tiers that the rate limiter buckets requests into.
+///
+/// `Strict` covers the expensive/write routes (transaction broadcast, block listings, record
+/// decryption); everything else falls under `Default`.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Tier {
+    Default,
+    Strict,
+}
+
+/// Returns the tier that the given request path falls under.
+fn tier_of(path: &str) -> Tier {
+    if path.ends_with("/transaction/broadcast") || path.ends_with("/blocks") || path.contains("/records/") {
+        Tier::Strict
+    } else {
+        Tier::Default
+    }
+}
+
+/// The requests/sec and burst size of a single token bucket tier.
+#[derive(Copy, Clone)]
+pub struct TierLimit {
+    /// The steady-state rate at which tokens are replenished.
+    pub requests_per_sec: f64,
+    /// The maximum number of tokens (and thus the largest burst) the bucket can hold.
+    pub burst: u32,
+}
+
+/// The rate limit configuration for the REST server.
+#[derive(Copy, Clone)]
+pub struct RateLimitConfig {
+    /// The limit applied to cheap, read-only metadata routes.
+    pub default: TierLimit,
+    /// The stricter limit applied to expensive or write routes.
+    pub strict: TierLimit,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            default: TierLimit { requests_per_sec: 50.0, burst: 100 },
+            strict: TierLimit { requests_per_sec: 5.0, burst: 10 },
+        }
+    }
+}
+
+/// How long a peer's bucket may sit idle before it's evicted.
+///
+/// A bucket that hasn't been touched in this long has long since refilled to its full burst
+/// size, so there's nothing useful left to remember about that peer.
+const BUCKET_IDLE_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// How many `check` calls to make between sweeps for idle buckets.
+///
+/// Sweeping is amortized across calls rather than done on every one, since a full scan of the
+/// map on every request would undercut the point of a cheap rate limiter.
+const SWEEP_EVERY_N_CALLS: u64 = 1024;
+
+/// A single peer's token bucket.
+struct Bucket {
+    /// The number of tokens currently available.
+    tokens: f64,
+    /// The last time the bucket was refilled.
+    last_refill: Instant,
+}
+
+impl Bucket {
+    fn new(limit: &TierLimit) -> Self {
+        Self { tokens: limit.burst as f64, last_refill: Instant::now() }
+    }
+
+    /// Refills the bucket based on elapsed time, then attempts to take one token.
+    ///
+    /// Returns `Ok(())` if the request is allowed, or `Err(retry_after)` if the peer must wait.
+    fn take(&mut self, limit: &TierLimit) -> Result<(), Duration> {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * limit.requests_per_sec).min(limit.burst as f64);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            let deficit = 1.0 - self.tokens;
+            Err(Duration::from_secs_f64(deficit / limit.requests_per_sec))
+        }
+    }
+}
+
+/// A tier's bucket map, with lazy eviction of peers that have gone idle.
+#[derive(Default)]
+struct BucketMap {
+    buckets: HashMap<SocketAddr, Bucket>,
+    calls_since_sweep: u64,
+}
+
+impl BucketMap {
+    /// Attempts to take a token for `addr`, periodically pruning idle buckets first so the map
+    /// doesn't grow without bound as distinct peers come and go.
+    fn take(&mut self, addr: SocketAddr, limit: &TierLimit) -> Result<(), Duration> {
+        self.calls_since_sweep += 1;
+        if self.calls_since_sweep >= SWEEP_EVERY_N_CALLS {
+            self.calls_since_sweep = 0;
+            let now = Instant::now();
+            self.buckets.retain(|_, bucket| now.duration_since(bucket.last_refill) < BUCKET_IDLE_TTL);
+        }
+
+        self.buckets.entry(addr).or_insert_with(|| Bucket::new(limit)).take(limit)
+    }
+}
+
+/// A per-IP, per-tier token-bucket rate limiter for the REST server.
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    default_buckets: Mutex<BucketMap>,
+    strict_buckets: Mutex<BucketMap>,
+}
+
+impl RateLimiter {
+    /// Initializes a new rate limiter with the given configuration.
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self { config, default_buckets: Default::default(), strict_buckets: Default::default() }
+    }
+
+    /// Attempts to take a token for `addr` under the tier matching `path`.
+    fn check(&self, addr: SocketAddr, path: &str) -> Result<(), Duration> {
+        let (limit, buckets) = match tier_of(path) {
+            Tier::Default => (&self.config.default, &self.default_buckets),
+            Tier::Strict => (&self.config.strict, &self.strict_buckets),
+        };
+
+        buckets.lock().take(addr, limit)
+    }
+}
+
+/// The middleware used to enforce per-IP rate limits across the REST server.
+pub async fn rate_limit_middleware<N: Network, C: 'static + ConsensusStorage<N>, R: Routing<N>, B: Send>(
+    State(rest): State<Rest<N, C, R>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    // If rate limiting isn't enabled, let the request through unconditionally.
+    let Some(limiter) = rest.rate_limiter() else {
+        return next.run(request).await.into_response();
+    };
+
+    match limiter.check(addr, request.uri().path()) {
+        Ok(()) => next.run(request).await.into_response(),
+        Err(retry_after) => {
+            let mut response = StatusCode::TOO_MANY_REQUESTS.into_response();
+            let retry_after_secs = retry_after.as_secs().max(1).to_string();
+            if let Ok(value) = HeaderValue::from_str(&retry_after_secs) {
+                response.headers_mut().insert(RETRY_AFTER, value);
+            }
+            response
+        }
+    }
+}
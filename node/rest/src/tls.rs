@@ -0,0 +1,69 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::Result;
+use axum_server::tls_rustls::RustlsConfig;
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+/// How often the certificate and key files are checked for changes.
+const CERT_WATCH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The paths to the PEM-encoded certificate and private key used to terminate REST connections.
+#[derive(Clone)]
+pub struct TlsConfig {
+    cert_path: PathBuf,
+    key_path: PathBuf,
+}
+
+impl TlsConfig {
+    /// Initializes a new TLS configuration from the given certificate and key file paths.
+    pub fn new(cert_path: PathBuf, key_path: PathBuf) -> Self {
+        Self { cert_path, key_path }
+    }
+
+    /// Loads the initial rustls server configuration from the configured files.
+    pub async fn load(&self) -> Result<RustlsConfig> {
+        Ok(RustlsConfig::from_pem_file(&self.cert_path, &self.key_path).await?)
+    }
+
+    /// Spawns a background task that watches the certificate and key files, reloading `config`
+    /// in place whenever either of them changes, so the REST server's certificate can be
+    /// rotated without restarting the node.
+    pub fn spawn_reloader(&self, config: RustlsConfig) -> tokio::task::JoinHandle<()> {
+        let tls = self.clone();
+        tokio::spawn(async move {
+            let mut last_modified = (modified_at(&tls.cert_path), modified_at(&tls.key_path));
+            loop {
+                tokio::time::sleep(CERT_WATCH_INTERVAL).await;
+
+                let modified = (modified_at(&tls.cert_path), modified_at(&tls.key_path));
+                if modified != last_modified {
+                    match config.reload_from_pem_file(&tls.cert_path, &tls.key_path).await {
+                        Ok(()) => info!("Reloaded the REST server's TLS certificate"),
+                        Err(error) => warn!("Failed to reload the REST server's TLS certificate: {error}"),
+                    }
+                    last_modified = modified;
+                }
+            }
+        })
+    }
+}
+
+/// Returns the last-modified time of the file at `path`, if it can be determined.
+fn modified_at(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
@@ -0,0 +1,151 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::Rest;
+
+use snarkos_node_consensus::Consensus;
+use snarkos_node_router::Routing;
+use snarkvm::{prelude::Network, synthesizer::ConsensusStorage};
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Query, State,
+    },
+    response::Response,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::{collections::HashSet, sync::Arc};
+use tokio::sync::broadcast;
+
+/// The bounded size of each subscriber's outbound event buffer.
+///
+/// A client that falls behind by more than this many pushed events is disconnected, rather than
+/// letting one slow websocket connection back-pressure the fan-out to everyone else.
+const SUBSCRIBER_BUFFER: usize = 1024;
+
+/// An event pushed to subscribers of the `/subscribe` websocket route.
+///
+/// `topic` is either `"blocks"` or `"transactions"`.
+///
+/// A `"mappingUpdates/{program_id}/{mapping}"` topic was originally planned, but nothing in this
+/// crate can observe individual mapping writes as a block commits (the ledger only exposes
+/// committed blocks and transactions, not a finalize-operation diff), so it was dropped rather
+/// than shipped as a topic clients could subscribe to and never receive anything on.
+pub struct PushEvent {
+    topic: String,
+    payload: Value,
+}
+
+/// The fan-out hub for the REST server's websocket subscriptions.
+///
+/// `Rest`'s block watcher calls [`Hub::publish`] for each newly committed block it sees when
+/// polling the ledger's height; every connected subscriber with a matching topic receives the
+/// event as a pushed JSON frame.
+pub struct Hub {
+    sender: broadcast::Sender<Arc<PushEvent>>,
+}
+
+impl Hub {
+    /// Initializes a new, empty hub.
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(SUBSCRIBER_BUFFER);
+        Self { sender }
+    }
+
+    /// Publishes `payload` to every subscriber listening on `topic`.
+    pub fn publish(&self, topic: impl Into<String>, payload: Value) {
+        // There may be no subscribers at all; a send error here just means nobody's listening.
+        let _ = self.sender.send(Arc::new(PushEvent { topic: topic.into(), payload }));
+    }
+}
+
+impl Default for Hub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The query parameters accepted by `/subscribe`.
+#[derive(Deserialize)]
+pub struct SubscribeQuery {
+    /// A comma-separated list of topics to subscribe to, e.g. `blocks,transactions`.
+    topics: Option<String>,
+}
+
+/// GET /testnet3/subscribe
+///
+/// Upgrades the connection to a websocket and streams pushed JSON frames for the requested
+/// topics (`blocks`, `transactions`), so that clients can follow the chain without polling.
+pub async fn subscribe<N: Network, C: 'static + ConsensusStorage<N>, R: Routing<N>>(
+    State(rest): State<Rest<N, C, R>>,
+    Query(query): Query<SubscribeQuery>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let topics: HashSet<String> = query
+        .topics
+        .unwrap_or_default()
+        .split(',')
+        .map(|topic| topic.trim().to_string())
+        .filter(|topic| !topic.is_empty())
+        .collect();
+
+    ws.on_upgrade(move |socket| handle_socket(socket, rest, topics))
+}
+
+/// Drives a single subscriber connection until it unsubscribes, disconnects, or falls too far
+/// behind the broadcast feed.
+async fn handle_socket<N: Network, C: 'static + ConsensusStorage<N>, R: Routing<N>>(
+    mut socket: WebSocket,
+    rest: Rest<N, C, R>,
+    topics: HashSet<String>,
+) {
+    let mut events = rest.hub().sender.subscribe();
+
+    loop {
+        tokio::select! {
+            event = events.recv() => {
+                match event {
+                    Ok(event) if topic_matches(&topics, &event.topic) => {
+                        let frame = json!({ "topic": event.topic, "data": event.payload }).to_string();
+                        if socket.send(Message::Text(frame)).await.is_err() {
+                            break;
+                        }
+                    }
+                    // The event didn't match any subscribed topic; nothing to push.
+                    Ok(_) => continue,
+                    // The client fell behind the broadcast buffer; drop it instead of replaying
+                    // a stale backlog.
+                    Err(broadcast::error::RecvError::Lagged(_)) => break,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            message = socket.recv() => {
+                // The client doesn't send anything meaningful post-handshake; pings and pongs
+                // are skipped, and any other incoming message (including a close frame or a
+                // transport error) ends the session.
+                match message {
+                    Some(Ok(Message::Ping(_) | Message::Pong(_))) => continue,
+                    _ => break,
+                }
+            }
+        }
+    }
+}
+
+/// Returns `true` if `topic` matches one of the client's subscribed topics.
+fn topic_matches(subscribed: &HashSet<String>, topic: &str) -> bool {
+    subscribed.contains(topic)
+}